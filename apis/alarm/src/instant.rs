@@ -0,0 +1,221 @@
+//! `Instant`/`Duration` time abstractions built on top of the raw
+//! `Ticks`/`Hz` syscall types, so callers can measure intervals and
+//! express deadlines without manually juggling tick frequencies.
+
+use core::cmp::Ordering;
+use core::marker::PhantomData;
+use libtock_platform as platform;
+use libtock_platform::{DefaultConfig, ErrorCode, Syscalls};
+
+use crate::{Alarm, Hz, Ticks};
+
+/// A point in time, captured from the alarm driver's free-running
+/// counter.
+///
+/// `Instant`s are only comparable to other `Instant`s created with the
+/// same `S`/`C`, since the tick count is only meaningful relative to a
+/// single frequency.
+pub struct Instant<S: Syscalls, C: platform::subscribe::Config = DefaultConfig> {
+    ticks: Ticks,
+    freq: Hz,
+    syscalls: PhantomData<(S, C)>,
+}
+
+// Implemented by hand rather than derived: a derive would add `S: Trait`/
+// `C: Trait` bounds even though both only ever appear inside `PhantomData`.
+impl<S: Syscalls, C: platform::subscribe::Config> Copy for Instant<S, C> {}
+
+impl<S: Syscalls, C: platform::subscribe::Config> Clone for Instant<S, C> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<S: Syscalls, C: platform::subscribe::Config> core::fmt::Debug for Instant<S, C> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Instant")
+            .field("ticks", &self.ticks.0)
+            .field("freq", &self.freq.0)
+            .finish()
+    }
+}
+
+impl<S: Syscalls, C: platform::subscribe::Config> Instant<S, C> {
+    /// Captures the current time.
+    pub fn now() -> Result<Self, ErrorCode> {
+        Ok(Instant {
+            ticks: Alarm::<S, C>::get_time()?,
+            freq: Alarm::<S, C>::get_frequency()?,
+            syscalls: PhantomData,
+        })
+    }
+
+    /// Returns the `Duration` elapsed since this `Instant` was
+    /// captured.
+    pub fn elapsed(&self) -> Result<Duration, ErrorCode> {
+        Ok(Self::now()? - *self)
+    }
+
+    /// Returns the `Instant` that is `duration` after this one, or
+    /// `None` if `duration` doesn't fit in a single tick period at
+    /// this frequency.
+    pub fn checked_add(&self, duration: Duration) -> Option<Self> {
+        let dt = u32::try_from(duration.to_ticks(self.freq)).ok()?;
+        Some(Instant {
+            ticks: self.ticks + Ticks(dt),
+            freq: self.freq,
+            syscalls: PhantomData,
+        })
+    }
+}
+
+impl<S: Syscalls, C: platform::subscribe::Config> core::ops::Sub for Instant<S, C> {
+    type Output = Duration;
+
+    fn sub(self, other: Self) -> Duration {
+        Duration::from_ticks((self.ticks - other.ticks).0 as u64, self.freq)
+    }
+}
+
+impl<S: Syscalls, C: platform::subscribe::Config> PartialEq for Instant<S, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.ticks.0 == other.ticks.0
+    }
+}
+
+impl<S: Syscalls, C: platform::subscribe::Config> Eq for Instant<S, C> {}
+
+impl<S: Syscalls, C: platform::subscribe::Config> PartialOrd for Instant<S, C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        // Ticks wrap at 2^32, so comparisons treat whichever instant is
+        // within half the range "ahead" of the other as the later one,
+        // the same way TCP sequence numbers are compared.
+        let diff = self.ticks.0.wrapping_sub(other.ticks.0);
+        Some(if diff == 0 {
+            Ordering::Equal
+        } else if diff < (1 << 31) {
+            Ordering::Greater
+        } else {
+            Ordering::Less
+        })
+    }
+}
+
+/// A span of time, independent of any particular alarm frequency.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Duration {
+    micros: u64,
+}
+
+impl Duration {
+    pub fn from_micros(micros: u64) -> Self {
+        Duration { micros }
+    }
+
+    pub fn from_millis(millis: u64) -> Self {
+        Duration::from_micros(millis.saturating_mul(1000))
+    }
+
+    pub fn from_secs(secs: u64) -> Self {
+        Duration::from_millis(secs.saturating_mul(1000))
+    }
+
+    pub fn as_micros(&self) -> u64 {
+        self.micros
+    }
+
+    /// Converts to a tick count at the given frequency, splitting the
+    /// multiplication the same way `Milliseconds::to_ticks` does so it
+    /// doesn't overflow for realistic inputs.
+    fn to_ticks(self, freq: Hz) -> u64 {
+        let whole_secs = self.micros / 1_000_000;
+        let rem_micros = self.micros % 1_000_000;
+        whole_secs.saturating_mul(freq.0 as u64)
+            + (rem_micros.saturating_mul(freq.0 as u64)) / 1_000_000
+    }
+
+    fn from_ticks(ticks: u64, freq: Hz) -> Self {
+        if freq.0 == 0 {
+            return Duration::from_micros(0);
+        }
+        let freq = freq.0 as u64;
+        let whole_secs = ticks / freq;
+        let rem_ticks = ticks % freq;
+        let micros = whole_secs
+            .saturating_mul(1_000_000)
+            .saturating_add(rem_ticks.saturating_mul(1_000_000) / freq);
+        Duration::from_micros(micros)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libtock_unittest::fake;
+
+    type Instant = super::Instant<fake::Syscalls, fake::Config>;
+
+    fn mk_instant(ticks: u32, freq: u32) -> Instant {
+        Instant {
+            ticks: Ticks(ticks),
+            freq: Hz(freq),
+            syscalls: PhantomData,
+        }
+    }
+
+    #[test]
+    fn duration_constructors_agree_on_units() {
+        assert_eq!(Duration::from_secs(1), Duration::from_millis(1000));
+        assert_eq!(Duration::from_millis(1), Duration::from_micros(1000));
+    }
+
+    #[test]
+    fn duration_round_trips_through_ticks() {
+        let freq = Hz(1_000_000);
+        let duration = Duration::from_millis(250);
+        let ticks = duration.to_ticks(freq);
+        assert_eq!(ticks, 250_000);
+        assert_eq!(Duration::from_ticks(ticks, freq), duration);
+    }
+
+    #[test]
+    fn now_and_elapsed() {
+        let kernel = fake::Kernel::new();
+        let driver = fake::Alarm::new(1);
+        kernel.add_driver(&driver);
+
+        let start = Instant::now().unwrap();
+        assert_eq!(
+            crate::Alarm::<fake::Syscalls, fake::Config>::sleep_for(crate::Milliseconds(5000)),
+            Ok(())
+        );
+        assert_eq!(start.elapsed().unwrap(), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn checked_add_advances_by_the_given_duration() {
+        let kernel = fake::Kernel::new();
+        let driver = fake::Alarm::new(1);
+        kernel.add_driver(&driver);
+
+        let start = Instant::now().unwrap();
+        let later = start.checked_add(Duration::from_secs(5)).unwrap();
+        assert_eq!(later - start, Duration::from_secs(5));
+    }
+
+    #[test]
+    fn partial_ord_just_under_half_range_is_greater() {
+        let other = mk_instant(0, 1);
+        let me = mk_instant((1u32 << 31) - 1, 1);
+        assert_eq!(me.partial_cmp(&other), Some(Ordering::Greater));
+    }
+
+    #[test]
+    fn partial_ord_at_half_range_is_less() {
+        // Exactly 2^31 apart is ambiguous; this crate resolves the tie
+        // by treating it as "behind" rather than "ahead".
+        let other = mk_instant(0, 1);
+        let me = mk_instant(1u32 << 31, 1);
+        assert_eq!(me.partial_cmp(&other), Some(Ordering::Less));
+    }
+}
@@ -7,6 +7,9 @@ use libtock_platform as platform;
 use libtock_platform::share;
 use libtock_platform::{DefaultConfig, ErrorCode, Syscalls};
 
+mod instant;
+pub use instant::{Duration, Instant};
+
 /// The alarm driver
 ///
 /// # Example
@@ -19,6 +22,15 @@ use libtock_platform::{DefaultConfig, ErrorCode, Syscalls};
 
 pub struct Alarm<S: Syscalls, C: platform::subscribe::Config = DefaultConfig>(S, C);
 
+/// The alarm driver's tick frequency, as reported by
+/// `command::FREQUENCY`.
+///
+/// Kernels whose hardware timer is narrower than 32 bits (e.g. a 24-bit
+/// counter) left-justify it: the value userspace sees still wraps at
+/// exactly `2^32`, and `FREQUENCY` is scaled up to match, so this is
+/// always the frequency of that scaled, left-justified value and never
+/// the native hardware frequency. All tick arithmetic in this crate
+/// assumes its inputs live in that left-justified domain.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct Hz(pub u32);
 
@@ -27,6 +39,10 @@ pub trait Convert {
     fn to_ticks(self, freq: Hz) -> Ticks;
 }
 
+/// A tick count in the alarm driver's left-justified, scaled time
+/// domain (see `Hz`). Ticks always wrap at `2^32` regardless of the
+/// underlying hardware timer's native width, so all arithmetic on
+/// `Ticks` (`Add`, `Sub`, elapsed-time calculations) is wrapping.
 #[derive(Copy, Clone, Debug)]
 pub struct Ticks(pub u32);
 
@@ -63,10 +79,6 @@ pub struct Milliseconds(pub u32);
 
 impl Convert for Milliseconds {
     fn to_ticks(self, freq: Hz) -> Ticks {
-        // Saturating multiplication will top out at about 1 hour at 1MHz.
-        // It's large enough for an alarm, and much simpler than failing
-        // or losing precision for short sleeps.
-
         /// u32::div_ceil is still unstable.
         fn div_ceil(a: u32, other: u32) -> u32 {
             let d = a / other;
@@ -77,10 +89,52 @@ impl Convert for Milliseconds {
                 d + 1
             }
         }
-        Ticks(div_ceil(self.0.saturating_mul(freq.0), 1000))
+
+        // Split into whole seconds and a sub-1000ms remainder before
+        // multiplying by `freq`, so `rem * freq` (rem < 1000) can't
+        // overflow u32 until freq exceeds ~4.29MHz, unlike multiplying
+        // the full millisecond count by freq first. `whole * freq` can
+        // still overflow for genuinely enormous durations, which is
+        // what the long-sleep chaining path (`sleep_for_long`) is for.
+        let whole = self.0 / 1000;
+        let rem = self.0 % 1000;
+        let ticks = whole
+            .saturating_mul(freq.0)
+            .saturating_add(div_ceil(rem.saturating_mul(freq.0), 1000));
+        Ticks(ticks)
+    }
+}
+
+/// Converts a time unit to a 64-bit tick count, for durations that may
+/// exceed what a single hardware alarm period (up to `u32::MAX` ticks)
+/// can represent.
+pub trait LongConvert {
+    fn to_ticks64(self, freq: Hz) -> u64;
+}
+
+#[derive(Copy, Clone)]
+pub struct Seconds(pub u64);
+
+impl LongConvert for Seconds {
+    fn to_ticks64(self, freq: Hz) -> u64 {
+        self.0.saturating_mul(freq.0 as u64)
     }
 }
 
+#[derive(Copy, Clone)]
+pub struct Minutes(pub u64);
+
+impl LongConvert for Minutes {
+    fn to_ticks64(self, freq: Hz) -> u64 {
+        Seconds(self.0.saturating_mul(60)).to_ticks64(freq)
+    }
+}
+
+/// The largest `dt` we'll ever pass to `command::SET_RELATIVE` in one
+/// go. Kept well under `u32::MAX` so a single hardware period can't be
+/// mistaken for having already elapsed by the time it's armed.
+const MAX_SAFE_DT: u32 = u32::MAX / 2;
+
 impl<S: Syscalls, C: platform::subscribe::Config> Alarm<S, C> {
     /// Run a check against the console capsule to ensure it is present.
     #[inline(always)]
@@ -101,14 +155,122 @@ impl<S: Syscalls, C: platform::subscribe::Config> Alarm<S, C> {
     }
 
     pub fn sleep_for<T: Convert>(time: T) -> Result<(), ErrorCode> {
+        let called: Cell<Option<(u32, u32)>> = Cell::new(None);
+        share::scope(|subscribe| {
+            S::subscribe::<_, _, C, DRIVER_NUM, { subscribe::CALLBACK }>(subscribe, &called)?;
+
+            Self::set_alarm(time)?;
+
+            loop {
+                S::yield_wait();
+                if let Some((_when, _ref)) = called.get() {
+                    return Ok(());
+                }
+            }
+        })
+    }
+
+    /// Arms the alarm to fire after `time` and returns immediately,
+    /// without waiting for it to fire or registering a callback.
+    ///
+    /// This is the non-blocking counterpart to `sleep_for`: it is
+    /// useful for arming a timeout that the caller may later cancel
+    /// with `stop` before it fires. This pair is poll-only: since
+    /// `set_alarm` doesn't subscribe, there is no upcall to learn the
+    /// alarm fired. Callers that need to know must race it with their
+    /// own `get_time()` checks (or use `sleep_for`/`sleep_for_waitfor`
+    /// if blocking until it fires is acceptable).
+    pub fn set_alarm<T: Convert>(time: T) -> Result<(), ErrorCode> {
         let freq = Self::get_frequency()?;
         let ticks = time.to_ticks(freq);
 
+        S::command(DRIVER_NUM, command::SET_RELATIVE, ticks.0, 0)
+            .to_result()
+            .map(|_when: u32| ())
+    }
+
+    /// Cancels a pending alarm previously armed with `set_alarm`, so it
+    /// never fires. There's no callback to unsubscribe from: `set_alarm`
+    /// never registers one, so this only issues `command::STOP`.
+    pub fn stop() -> Result<(), ErrorCode> {
+        S::command(DRIVER_NUM, command::STOP, 0, 0).to_result()
+    }
+
+    /// Sleeps for a duration that may be longer than a single hardware
+    /// alarm period can express.
+    ///
+    /// Keeps a 64-bit remaining-ticks counter and chains together
+    /// however many `SET_RELATIVE` alarms (each at most `MAX_SAFE_DT`)
+    /// are needed to cover it, re-arming after each wake-up until the
+    /// remaining count reaches zero.
+    pub fn sleep_for_long<T: LongConvert>(time: T) -> Result<(), ErrorCode> {
+        let freq = Self::get_frequency()?;
+        let mut remaining = time.to_ticks64(freq);
+
+        while remaining > 0 {
+            let reference = Self::get_time()?;
+            let dt = remaining.min(MAX_SAFE_DT as u64) as u32;
+
+            let called: Cell<Option<(u32, u32)>> = Cell::new(None);
+            share::scope(|subscribe| {
+                S::subscribe::<_, _, C, DRIVER_NUM, { subscribe::CALLBACK }>(subscribe, &called)?;
+
+                S::command(DRIVER_NUM, command::SET_RELATIVE, dt, 0)
+                    .to_result()
+                    .map(|_when: u32| ())?;
+
+                loop {
+                    S::yield_wait();
+                    if called.get().is_some() {
+                        return Ok(());
+                    }
+                }
+            })?;
+
+            let elapsed = (Self::get_time()? - reference).0 as u64;
+            remaining = remaining.saturating_sub(elapsed.max(dt as u64));
+        }
+
+        Ok(())
+    }
+
+    /// Sleeps until the given absolute deadline is reached.
+    ///
+    /// Unlike `sleep_for`, the deadline is not computed from the current
+    /// time, so callers can schedule back-to-back wake-ups (e.g. a
+    /// periodic timer) as `previous_deadline + period` without
+    /// accumulating the wake-up latency of each cycle into the next one.
+    ///
+    /// If `deadline` is already behind `reference` (e.g. a prior
+    /// iteration overran its period), this arms the alarm to fire
+    /// immediately instead of wrapping `dt` around to almost a full
+    /// tick cycle away.
+    pub fn sleep_until(deadline: Ticks) -> Result<(), ErrorCode> {
+        let reference = Self::get_time()?;
+        let dt = deadline - reference;
+        // Ticks wrap at 2^32: treat a `dt` in the upper half of the
+        // range as "actually negative" (deadline already passed) rather
+        // than as a near-full-cycle wait, the same half-range test used
+        // by `Instant::partial_cmp`.
+        let dt = if dt.0 < (1 << 31) { dt } else { Ticks(0) };
+        Self::set_alarm_at(reference, dt)
+    }
+
+    /// Arms the alarm to fire `dt` ticks after `reference` and blocks
+    /// until it does, using `command::SET_ABSOLUTE`.
+    ///
+    /// This is the lower-level primitive `sleep_until` is built on;
+    /// it's exposed directly for callers that already have a
+    /// `reference` tick (e.g. from `get_time`) and want to avoid
+    /// `sleep_until`'s extra `get_time` call.
+    pub fn set_alarm_at(reference: Ticks, dt: Ticks) -> Result<(), ErrorCode> {
+        let target = reference + dt;
+
         let called: Cell<Option<(u32, u32)>> = Cell::new(None);
         share::scope(|subscribe| {
             S::subscribe::<_, _, C, DRIVER_NUM, { subscribe::CALLBACK }>(subscribe, &called)?;
 
-            S::command(DRIVER_NUM, command::SET_RELATIVE, ticks.0, 0)
+            S::command(DRIVER_NUM, command::SET_ABSOLUTE, target.0, 0)
                 .to_result()
                 .map(|_when: u32| ())?;
 
@@ -122,6 +284,32 @@ impl<S: Syscalls, C: platform::subscribe::Config> Alarm<S, C> {
     }
 }
 
+/// Marker trait implemented by a `Syscalls` backend for kernels that
+/// support the "yield-wait-for" syscall variant: a yield that blocks
+/// until a specific driver/subscription's upcall is ready and returns
+/// its data directly, instead of waking on every upcall and requiring
+/// userspace to check a shared buffer. This is a property of the
+/// kernel/syscall backend, not of the upcall-safety `subscribe::Config`,
+/// so it's gated on `S` rather than `C`. Backends without it must keep
+/// using the ordinary `subscribe` + `yield_wait` loop, so this is opt-in
+/// rather than autodetected.
+pub trait SupportsYieldWaitFor: Syscalls {}
+
+impl<S: Syscalls + SupportsYieldWaitFor, C: platform::subscribe::Config> Alarm<S, C> {
+    /// Sleeps for `time`, like `sleep_for`, but uses `yield-wait-for`
+    /// instead of `subscribe` + `yield_wait`.
+    ///
+    /// This avoids allocating the `Cell` and entering a `share::scope`
+    /// to receive the callback: the alarm is armed and the next yield
+    /// blocks until that specific upcall is ready, returning its data
+    /// directly with no persistent callback registration.
+    pub fn sleep_for_waitfor<T: Convert>(time: T) -> Result<(), ErrorCode> {
+        Self::set_alarm(time)?;
+        S::yield_wait_for::<DRIVER_NUM, { subscribe::CALLBACK }>();
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests;
 
@@ -0,0 +1,207 @@
+use super::*;
+
+#[test]
+fn ticks_passthrough() {
+    assert_eq!(Ticks(1234).to_ticks(Hz(1_000_000)).0, 1234);
+}
+
+#[test]
+fn ticks_wrapping_add_and_sub() {
+    assert_eq!((Ticks(u32::MAX) + Ticks(1)).0, 0);
+    assert_eq!((Ticks(0) - Ticks(1)).0, u32::MAX);
+}
+
+#[test]
+fn millis_to_ticks_exact() {
+    // 10ms at 1MHz is exactly 10_000 ticks, no rounding involved.
+    assert_eq!(Milliseconds(10).to_ticks(Hz(1_000_000)).0, 10_000);
+}
+
+#[test]
+fn millis_to_ticks_rounds_up() {
+    // 1ms at 1500Hz is 1.5 ticks, which should round up to 2.
+    assert_eq!(Milliseconds(1).to_ticks(Hz(1_500)).0, 2);
+}
+
+#[test]
+fn millis_to_ticks_does_not_overflow_past_old_saturation_point() {
+    // Under the previous `ms.saturating_mul(freq)` implementation, this
+    // was the smallest millisecond count that overflowed u32 at 1MHz
+    // (4295 * 1_000_000 > u32::MAX) and silently clamped to a
+    // nonsensical tick count instead of the correct ~4.295 million.
+    assert_eq!(Milliseconds(4295).to_ticks(Hz(1_000_000)).0, 4_295_000);
+}
+
+#[test]
+fn millis_to_ticks_near_max_supported_frequency() {
+    // With freq close to 4.29MHz, `rem * freq` (rem < 1000) is close to
+    // but must stay under u32::MAX.
+    let freq = Hz(4_294_967);
+    // rem = 999, so rem * freq = 4_290_672_033, comfortably under
+    // u32::MAX (4_294_967_295).
+    let ticks = Milliseconds(1_999).to_ticks(freq);
+    assert_eq!(ticks.0, freq.0 + div_ceil_for_test(999 * freq.0, 1000));
+}
+
+#[test]
+fn elapsed_wraps_at_2_32_not_at_the_native_timer_width() {
+    // Simulates a kernel whose hardware timer is narrower than 32 bits
+    // (e.g. 24-bit) and left-justified: `FREQUENCY` reports the scaled
+    // value, but the tick counter itself still wraps at exactly 2^32.
+    // `now - reference` must use that full 32-bit domain rather than
+    // assuming a narrower native wraparound.
+    let reference = Ticks(u32::MAX - 99);
+    let now = Ticks(100); // wrapped around past u32::MAX
+    assert_eq!((now - reference).0, 200);
+}
+
+fn div_ceil_for_test(a: u32, b: u32) -> u32 {
+    let d = a / b;
+    let m = a % b;
+    if m == 0 {
+        d
+    } else {
+        d + 1
+    }
+}
+
+// `sleep_for_long`'s chaining loop needs a fake kernel that actually
+// advances its clock as alarms fire, so it's exercised separately from
+// the pure conversion tests above.
+mod sleep_for_long {
+    use super::*;
+    use libtock_unittest::fake;
+
+    type Alarm = super::super::Alarm<fake::Syscalls, fake::Config>;
+
+    // A 1Hz fake alarm makes `Seconds(n)` convert to exactly `n` ticks,
+    // so a chain length can be driven precisely off `MAX_SAFE_DT`
+    // without needing to fake the hardware's native timer width.
+    fn one_hz_kernel() -> (fake::Kernel, fake::Alarm) {
+        let kernel = fake::Kernel::new();
+        let driver = fake::Alarm::new(1);
+        kernel.add_driver(&driver);
+        (kernel, driver)
+    }
+
+    #[test]
+    fn single_period() {
+        let (_kernel, _driver) = one_hz_kernel();
+        assert_eq!(Alarm::sleep_for_long(Seconds(10)), Ok(()));
+        assert_eq!(Alarm::get_time().map(|t| t.0), Ok(10));
+    }
+
+    #[test]
+    fn two_chained_periods() {
+        let (_kernel, _driver) = one_hz_kernel();
+        let total = super::super::MAX_SAFE_DT as u64 + 1;
+
+        assert_eq!(Alarm::sleep_for_long(Seconds(total)), Ok(()));
+        assert_eq!(Alarm::get_time().map(|t| t.0), Ok(total as u32));
+    }
+
+    #[test]
+    fn three_chained_periods() {
+        let (_kernel, _driver) = one_hz_kernel();
+        let total = 2 * super::super::MAX_SAFE_DT as u64 + 1;
+        assert_eq!(total, u32::MAX as u64, "test expects an exact u32::MAX total");
+
+        assert_eq!(Alarm::sleep_for_long(Seconds(total)), Ok(()));
+        assert_eq!(Alarm::get_time().map(|t| t.0), Ok(total as u32));
+    }
+}
+
+mod sleep_until {
+    use super::*;
+    use libtock_unittest::fake;
+
+    type Alarm = super::super::Alarm<fake::Syscalls, fake::Config>;
+
+    fn one_hz_kernel() -> (fake::Kernel, fake::Alarm) {
+        let kernel = fake::Kernel::new();
+        let driver = fake::Alarm::new(1);
+        kernel.add_driver(&driver);
+        (kernel, driver)
+    }
+
+    #[test]
+    fn set_alarm_at_adds_reference_and_dt() {
+        let (_kernel, _driver) = one_hz_kernel();
+        assert_eq!(Alarm::set_alarm_at(Ticks(3), Ticks(7)), Ok(()));
+        assert_eq!(Alarm::get_time().map(|t| t.0), Ok(10));
+    }
+
+    #[test]
+    fn sleeps_until_a_future_deadline() {
+        let (_kernel, _driver) = one_hz_kernel();
+        assert_eq!(Alarm::sleep_until(Ticks(10)), Ok(()));
+        assert_eq!(Alarm::get_time().map(|t| t.0), Ok(10));
+    }
+
+    #[test]
+    fn arms_immediately_when_the_deadline_already_passed() {
+        let (_kernel, _driver) = one_hz_kernel();
+        // Move the clock past the deadline we're about to ask for.
+        assert_eq!(Alarm::sleep_until(Ticks(10)), Ok(()));
+        assert_eq!(Alarm::get_time().map(|t| t.0), Ok(10));
+
+        // Without the half-range fix, `deadline - reference` would wrap
+        // to almost u32::MAX and arm the hardware for nearly a full
+        // tick cycle instead of firing right away.
+        assert_eq!(Alarm::sleep_until(Ticks(5)), Ok(()));
+        assert_eq!(Alarm::get_time().map(|t| t.0), Ok(10));
+    }
+}
+
+mod set_alarm {
+    use super::*;
+    use libtock_unittest::fake;
+
+    type Alarm = super::super::Alarm<fake::Syscalls, fake::Config>;
+
+    fn one_hz_kernel() -> (fake::Kernel, fake::Alarm) {
+        let kernel = fake::Kernel::new();
+        let driver = fake::Alarm::new(1);
+        kernel.add_driver(&driver);
+        (kernel, driver)
+    }
+
+    #[test]
+    fn arms_the_driver() {
+        let (_kernel, driver) = one_hz_kernel();
+        assert_eq!(Alarm::set_alarm(Ticks(10)), Ok(()));
+        assert!(driver.is_enabled());
+    }
+
+    #[test]
+    fn stop_disarms_a_pending_alarm() {
+        let (_kernel, driver) = one_hz_kernel();
+        assert_eq!(Alarm::set_alarm(Ticks(10)), Ok(()));
+        assert_eq!(Alarm::stop(), Ok(()));
+        assert!(!driver.is_enabled());
+    }
+}
+
+mod sleep_for_waitfor {
+    use super::*;
+    use libtock_unittest::fake;
+
+    // `fake::Syscalls` services a pending alarm command the same way
+    // regardless of which yield variant is used to wait on it, so it's
+    // safe to opt it into `SupportsYieldWaitFor` for this test; a real
+    // board's `Syscalls` impl should only do the same if its kernel
+    // actually implements yield-wait-for.
+    impl super::super::SupportsYieldWaitFor for fake::Syscalls {}
+
+    type Alarm = super::super::Alarm<fake::Syscalls, fake::Config>;
+
+    #[test]
+    fn sleeps_for_the_requested_duration() {
+        let kernel = fake::Kernel::new();
+        let driver = fake::Alarm::new(1);
+        kernel.add_driver(&driver);
+
+        assert_eq!(Alarm::sleep_for_waitfor(Ticks(10)), Ok(()));
+        assert_eq!(Alarm::get_time().map(|t| t.0), Ok(10));
+    }
+}